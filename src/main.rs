@@ -1,7 +1,7 @@
 #![warn(clippy::all, clippy::pedantic)]
 
 //! A command-line utility that counts file extensions from a list of paths.
-//! 
+//!
 //! # Description
 //! This program reads file paths from standard input, one per line, and counts
 //! the occurrences of each file extension. Extensions are converted to lowercase
@@ -18,49 +18,515 @@
 //! [no extension]: count
 //! ```
 //!
+//! # Grouping
+//! Passing `--group` rolls extensions up into broad categories (Images, Audio,
+//! Videos, Documents, Archives) instead of listing every extension individually.
+//! Extensions that don't belong to a known category are tallied under `[other]`.
+//!
+//! # Verification
+//! Passing `--verify` sniffs the leading magic bytes of each path that exists
+//! on disk and compares the inferred real type against the declared extension,
+//! reporting a separate "mismatches" section for files whose extension lies
+//! about their type (e.g. a `.png` that's actually a JPEG). Equivalent
+//! spellings of the same type (`.jpeg`/`.jpg`, `.tif`/`.tiff`) are treated
+//! as a match rather than flagged as lying.
+//!
+//! # Directory Walking
+//! Passing one or more directory arguments makes the tool walk them itself
+//! and count every file it finds, instead of reading paths from stdin. Stdin
+//! is still used when no path arguments are given, so `find | count_exts`
+//! keeps working.
+//!
+//! # Well-Known Filenames
+//! Extensionless files with a canonical name (`Makefile`, `Dockerfile`,
+//! `CMakeLists.txt`, `Rakefile`, `.gitignore`, `LICENSE`, `README`) are
+//! tallied under their own label instead of being lumped into the generic
+//! "[no extension]" bucket. Matching is case-insensitive and takes
+//! precedence over ordinary extension handling.
+//!
+//! # Output Formats
+//! `--format` selects how the counts are rendered: `text` (the default,
+//! human-readable `label: count` lines), `json` (a sorted array of
+//! `{ "extension": ..., "count": ... }` objects), or `csv` (`extension,count`
+//! rows with a header).
+//!
+//! # Parallel Counting
+//! Paths are tallied across a rayon thread pool: each worker keeps its own
+//! local counts and mismatches, which are folded into combined maps at the
+//! end. `--jobs N` pins the pool to `N` threads; omitted, rayon picks a
+//! default based on available parallelism.
+//!
+//! # Filtering
+//! `--include` and `--exclude` take comma-separated lists of extensions and
+//! restrict which files are counted. Uppercase category macros (`IMAGE`,
+//! `AUDIO`, `VIDEO`, `DOCUMENT`, `ARCHIVE`) expand to the same extension
+//! sets used by `--group`, so `--include IMAGE,VIDEO` answers "how many
+//! image or video files are here" without post-processing.
+//!
 //! # Error Handling
 //! - Handles invalid UTF-8 in file paths through Result/anyhow
 //! - Gracefully handles empty lines in input by skipping them
 //! - Safely processes files without extensions
 
 use anyhow::Result;
-use std::collections::HashMap;
-use std::io::{self, BufRead};
-use std::path::Path;
+use clap::Parser;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Read};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let mut counts: HashMap<String, usize> = HashMap::new();
-    let stdin = io::stdin();
-    let handle = stdin.lock();
-
-    // Read paths from stdin
-    for line in handle.lines() {
-        let path = line?.trim().to_string();
-        if path.is_empty() {
-            continue;
+/// Command-line arguments for `count_exts`.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Directories to walk for files. When omitted, paths are read from stdin.
+    paths: Vec<PathBuf>,
+
+    /// Roll extensions up into broad categories (Images, Audio, Videos,
+    /// Documents, Archives) instead of listing every extension individually.
+    #[arg(long)]
+    group: bool,
+
+    /// Sniff magic bytes of existing files and report extensions that
+    /// disagree with the real, detected type.
+    #[arg(long)]
+    verify: bool,
+
+    /// Maximum depth to descend when walking directory arguments.
+    #[arg(long = "max-depth")]
+    max_depth: Option<usize>,
+
+    /// Follow symlinks when walking directory arguments.
+    #[arg(long = "follow-symlinks")]
+    follow_symlinks: bool,
+
+    /// Output format for the counts.
+    #[arg(long, value_enum, default_value = "text")]
+    format: OutputFormat,
+
+    /// Number of worker threads to count with. Defaults to rayon's choice
+    /// based on available parallelism. Must be at least 1.
+    #[arg(long)]
+    jobs: Option<usize>,
+
+    /// Comma-separated extensions (or macros like `IMAGE`, `VIDEO`) to
+    /// restrict counting to.
+    #[arg(long)]
+    include: Option<String>,
+
+    /// Comma-separated extensions (or macros like `IMAGE`, `VIDEO`) to
+    /// exclude from counting.
+    #[arg(long)]
+    exclude: Option<String>,
+}
+
+/// The output format selected by `--format`.
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// A single row of the rendered output: a label (extension, category, or
+/// canonical filename) paired with its count.
+#[derive(Serialize)]
+struct ExtensionCount {
+    extension: String,
+    count: usize,
+}
+
+/// Renders `rows` to stdout in the requested `format`.
+fn render_rows(rows: &[(String, usize)], format: &OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            for (label, count) in rows {
+                println!("{label}: {count}");
+            }
         }
+        OutputFormat::Json => {
+            let entries: Vec<ExtensionCount> = rows
+                .iter()
+                .map(|(label, count)| ExtensionCount {
+                    extension: label.clone(),
+                    count: *count,
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&entries)?);
+        }
+        OutputFormat::Csv => {
+            println!("extension,count");
+            for (label, count) in rows {
+                println!("{label},{count}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Extensionless filenames with a canonical identity, mapped from their
+/// lowercase form to the label they're reported under.
+const KNOWN_FILENAMES: &[(&str, &str)] = &[
+    ("makefile", "Makefile"),
+    ("dockerfile", "Dockerfile"),
+    ("cmakelists.txt", "CMakeLists.txt"),
+    ("rakefile", "Rakefile"),
+    (".gitignore", ".gitignore"),
+    ("license", "LICENSE"),
+    ("readme", "README"),
+];
+
+/// Builds a lowercase lookup from filename to the label it's reported under.
+fn build_filename_lookup() -> HashMap<&'static str, &'static str> {
+    KNOWN_FILENAMES.iter().copied().collect()
+}
 
-        // Extract extension (in lowercase) or use empty string if none
-        let ext = Path::new(&path)
-            .extension()
-            .and_then(|e| e.to_str())
-            .map_or_else(String::new, str::to_lowercase);
+/// Looks up `path`'s filename (case-insensitively) in `lookup`, returning its
+/// display label if it's a recognized canonical filename.
+fn classify_filename(
+    path: &Path,
+    lookup: &HashMap<&'static str, &'static str>,
+) -> Option<&'static str> {
+    let file_name = path.file_name()?.to_str()?.to_lowercase();
+    lookup.get(file_name.as_str()).copied()
+}
+
+/// Returns whether `label` is one of the display labels in `KNOWN_FILENAMES`,
+/// as opposed to a raw extension or the empty "no extension" bucket.
+fn is_known_filename_label(label: &str) -> bool {
+    KNOWN_FILENAMES.iter().any(|(_, display)| *display == label)
+}
 
-        *counts.entry(ext).or_insert(0) += 1;
+/// Walks `dirs` with `walkdir`, honoring `max_depth` and `follow_links`, and
+/// returns the string path of every regular file encountered.
+fn walk_paths(dirs: &[PathBuf], max_depth: Option<usize>, follow_links: bool) -> Vec<String> {
+    let mut paths = Vec::new();
+
+    for dir in dirs {
+        let mut walker = WalkDir::new(dir).follow_links(follow_links);
+        if let Some(depth) = max_depth {
+            walker = walker.max_depth(depth);
+        }
+
+        for entry in walker.into_iter().filter_map(Result::ok) {
+            if entry.file_type().is_file() {
+                paths.push(entry.path().to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    paths
+}
+
+/// Equivalent extension spellings that shouldn't be reported as `--verify`
+/// mismatches against each other (e.g. a `.jpeg` file whose sniffed magic
+/// bytes come back as `"jpg"` is not lying about its type).
+const EXTENSION_ALIASES: &[(&str, &str)] = &[("jpeg", "jpg"), ("tif", "tiff")];
+
+/// Maps `ext` to its canonical spelling per `EXTENSION_ALIASES`, so aliased
+/// extensions compare equal regardless of which spelling was used.
+fn canonical_ext(ext: &str) -> &str {
+    EXTENSION_ALIASES
+        .iter()
+        .find_map(|(alias, canonical)| (*alias == ext).then_some(*canonical))
+        .unwrap_or(ext)
+}
+
+/// Reads the leading bytes of `path` and infers its real type from its
+/// magic bytes, returning the detected extension (e.g. `"png"`, `"gz"`).
+///
+/// Returns `None` if the file can't be opened or its type isn't recognized.
+fn detect_real_ext(path: &Path) -> Option<String> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut buf = [0u8; 8192];
+    let n = file.read(&mut buf).ok()?;
+    infer::get(&buf[..n]).map(|kind| kind.extension().to_string())
+}
+
+/// A broad category that extensions can be rolled up into with `--group`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Category {
+    Images,
+    Audio,
+    Videos,
+    Documents,
+    Archives,
+}
+
+impl Category {
+    /// All known categories, in display order.
+    const ALL: [Category; 5] = [
+        Category::Images,
+        Category::Audio,
+        Category::Videos,
+        Category::Documents,
+        Category::Archives,
+    ];
+
+    /// The lowercase, dot-less extensions that belong to this category.
+    fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Category::Images => &["png", "jpg", "jpeg", "gif", "bmp", "tiff", "svg", "webp"],
+            Category::Audio => &["mp3", "flac", "wav", "ogg", "m4a", "aac"],
+            Category::Videos => &["mp4", "mkv", "mov", "webm", "avi", "flv"],
+            Category::Documents => &["pdf", "doc", "docx", "txt", "md", "odt", "rtf"],
+            Category::Archives => &["zip", "tar", "gz", "7z", "rar", "bz2", "xz"],
+        }
+    }
+
+    /// The label printed for this category.
+    fn label(self) -> &'static str {
+        match self {
+            Category::Images => "Images",
+            Category::Audio => "Audio",
+            Category::Videos => "Videos",
+            Category::Documents => "Documents",
+            Category::Archives => "Archives",
+        }
+    }
+}
+
+/// Builds a reverse lookup from extension to the category it belongs to.
+fn build_category_lookup() -> HashMap<&'static str, Category> {
+    let mut lookup = HashMap::new();
+    for category in Category::ALL {
+        for ext in category.extensions() {
+            lookup.insert(*ext, category);
+        }
     }
+    lookup
+}
 
-    // Print counts sorted by count
-    let mut counts: Vec<_> = counts.into_iter().collect();
-    counts.sort_by_key(|(_, count)| *count);
+/// Aggregates raw per-extension counts into per-category counts, with
+/// unmapped extensions falling into a residual `[other]` bucket.
+fn group_by_category(counts: &HashMap<String, usize>) -> HashMap<&'static str, usize> {
+    let lookup = build_category_lookup();
+    let mut grouped: HashMap<&'static str, usize> = HashMap::new();
 
     for (ext, count) in counts {
-        let ext_display = if ext.is_empty() {
-            "[no extension]".to_string()
+        let label = lookup
+            .get(ext.as_str())
+            .copied()
+            .map_or("[other]", Category::label);
+        *grouped.entry(label).or_insert(0) += count;
+    }
+
+    grouped
+}
+
+/// Uppercase macro names for `--include`/`--exclude`, each expanding to the
+/// same extension set as the matching `--group` category.
+const FILTER_MACROS: &[(&str, Category)] = &[
+    ("IMAGE", Category::Images),
+    ("AUDIO", Category::Audio),
+    ("VIDEO", Category::Videos),
+    ("DOCUMENT", Category::Documents),
+    ("ARCHIVE", Category::Archives),
+];
+
+/// Parses a comma-separated `--include`/`--exclude` list into a set of
+/// lowercase, dot-less extensions, expanding any uppercase category macros
+/// (e.g. `IMAGE` -> `png,jpg,...`) and trimming dots/whitespace from the
+/// rest.
+fn parse_filter_list(raw: &str) -> HashSet<String> {
+    let mut extensions = HashSet::new();
+
+    for token in raw.split(',') {
+        let token = token.trim();
+        if token.is_empty() {
+            continue;
+        }
+
+        if let Some((_, category)) = FILTER_MACROS
+            .iter()
+            .find(|(name, _)| *name == token.to_uppercase())
+        {
+            extensions.extend(category.extensions().iter().map(|ext| (*ext).to_string()));
         } else {
-            format!(".{ext}")
-        };
-        println!("{ext_display}: {count}");
+            let cleaned = token.trim_start_matches('.').trim().to_lowercase();
+            if !cleaned.is_empty() {
+                extensions.insert(cleaned);
+            }
+        }
+    }
+
+    extensions
+}
+
+/// Classifies a single path into its counting bucket and, if `verify` is
+/// set and the path is an existing file whose sniffed type disagrees with
+/// its declared extension, a `(declared, actual)` mismatch pair.
+fn classify_path(
+    path: &str,
+    verify: bool,
+    filename_lookup: &HashMap<&'static str, &'static str>,
+    include: Option<&HashSet<String>>,
+    exclude: Option<&HashSet<String>>,
+) -> Option<(String, Option<(String, String)>)> {
+    if path.is_empty() {
+        return None;
+    }
+
+    let as_path = Path::new(path);
+
+    // Extract extension (in lowercase) or use empty string if none
+    let ext = as_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map_or_else(String::new, str::to_lowercase);
+
+    if include.is_some_and(|include| !include.contains(&ext)) {
+        return None;
+    }
+    if exclude.is_some_and(|exclude| exclude.contains(&ext)) {
+        return None;
+    }
+
+    let mismatch = (verify && as_path.is_file())
+        .then(|| detect_real_ext(as_path))
+        .flatten()
+        .filter(|actual| canonical_ext(actual) != canonical_ext(&ext))
+        .map(|actual| (ext.clone(), actual));
+
+    // Canonical filenames (Makefile, .gitignore, ...) take precedence
+    // over both the detected extension and "[no extension]".
+    let bucket = classify_filename(as_path, filename_lookup).map_or(ext, str::to_string);
+
+    Some((bucket, mismatch))
+}
+
+/// Counts and verifies every path in parallel across a rayon thread pool.
+/// Each worker accumulates into its own local maps, which are folded into
+/// one combined `(counts, mismatches)` pair at the end.
+fn count_paths_parallel(
+    paths: &[String],
+    verify: bool,
+    filename_lookup: &HashMap<&'static str, &'static str>,
+    include: Option<&HashSet<String>>,
+    exclude: Option<&HashSet<String>>,
+) -> (HashMap<String, usize>, HashMap<(String, String), usize>) {
+    paths
+        .par_iter()
+        .fold(
+            || {
+                (
+                    HashMap::<String, usize>::new(),
+                    HashMap::<(String, String), usize>::new(),
+                )
+            },
+            |(mut counts, mut mismatches), path| {
+                if let Some((bucket, mismatch)) =
+                    classify_path(path, verify, filename_lookup, include, exclude)
+                {
+                    *counts.entry(bucket).or_insert(0) += 1;
+                    if let Some(pair) = mismatch {
+                        *mismatches.entry(pair).or_insert(0) += 1;
+                    }
+                }
+                (counts, mismatches)
+            },
+        )
+        .reduce(
+            || (HashMap::new(), HashMap::new()),
+            |(mut counts, mut mismatches), (other_counts, other_mismatches)| {
+                for (bucket, count) in other_counts {
+                    *counts.entry(bucket).or_insert(0) += count;
+                }
+                for (pair, count) in other_mismatches {
+                    *mismatches.entry(pair).or_insert(0) += count;
+                }
+                (counts, mismatches)
+            },
+        )
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let filename_lookup = build_filename_lookup();
+
+    // Walk directory arguments if any were given, otherwise fall back to stdin
+    let paths: Vec<String> = if cli.paths.is_empty() {
+        let stdin = io::stdin();
+        let handle = stdin.lock();
+        handle
+            .lines()
+            .collect::<io::Result<Vec<_>>>()?
+            .into_iter()
+            .map(|line| line.trim().to_string())
+            .collect()
+    } else {
+        walk_paths(&cli.paths, cli.max_depth, cli.follow_symlinks)
+    };
+
+    let include = cli.include.as_deref().map(parse_filter_list);
+    let exclude = cli.exclude.as_deref().map(parse_filter_list);
+
+    let (counts, mismatches) = if let Some(jobs) = cli.jobs {
+        if jobs == 0 {
+            anyhow::bail!("--jobs must be at least 1");
+        }
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?;
+        pool.install(|| {
+            count_paths_parallel(
+                &paths,
+                cli.verify,
+                &filename_lookup,
+                include.as_ref(),
+                exclude.as_ref(),
+            )
+        })
+    } else {
+        count_paths_parallel(
+            &paths,
+            cli.verify,
+            &filename_lookup,
+            include.as_ref(),
+            exclude.as_ref(),
+        )
+    };
+
+    let mut rows: Vec<(String, usize)> = if cli.group {
+        group_by_category(&counts)
+            .into_iter()
+            .map(|(label, count)| (label.to_string(), count))
+            .collect()
+    } else {
+        counts
+            .into_iter()
+            .map(|(bucket, count)| {
+                let display = if is_known_filename_label(&bucket) {
+                    bucket
+                } else if bucket.is_empty() {
+                    "[no extension]".to_string()
+                } else {
+                    format!(".{bucket}")
+                };
+                (display, count)
+            })
+            .collect()
+    };
+    rows.sort_by_key(|(_, count)| *count);
+
+    render_rows(&rows, &cli.format)?;
+
+    if cli.verify {
+        let mut mismatches: Vec<_> = mismatches.into_iter().collect();
+        mismatches.sort_by_key(|(_, count)| *count);
+
+        println!("\nmismatches:");
+        for ((declared, actual), count) in mismatches {
+            let declared_display = if declared.is_empty() {
+                "[no extension]".to_string()
+            } else {
+                format!(".{declared}")
+            };
+            println!("{declared_display} -> .{actual}: {count}");
+        }
     }
 
     Ok(())
@@ -70,67 +536,256 @@ async fn main() -> Result<()> {
 mod tests {
     use super::*;
 
-    /// Helper function that simulates the main program's extension counting logic
-    /// for testing purposes.
-    ///
-    /// # Arguments
-    /// * `input` - A slice of string slices representing file paths to process
-    ///
-    /// # Returns
-    /// A HashMap containing extension counts, where the key is the lowercase extension
-    /// (or empty string for no extension) and the value is the count of occurrences.
-    async fn count_extensions(input: &[&str]) -> HashMap<String, usize> {
-        let mut counts: HashMap<String, usize> = HashMap::new();
-        for path in input {
-            if path.is_empty() {
-                continue;
-            }
-            let ext = Path::new(path)
-                .extension()
-                .and_then(|e| e.to_str())
-                .map(|e| e.to_lowercase())
-                .unwrap_or_else(|| String::new());
-            *counts.entry(ext).or_insert(0) += 1;
-        }
-        counts
+    /// Runs `input` through the real counting path (`count_paths_parallel`,
+    /// same as `main`) with no verification or filtering, for tests that
+    /// only care about the resulting bucket counts.
+    fn count_paths(input: &[&str]) -> HashMap<String, usize> {
+        let paths: Vec<String> = input.iter().map(|s| (*s).to_string()).collect();
+        let lookup = build_filename_lookup();
+        count_paths_parallel(&paths, false, &lookup, None, None).0
     }
 
-    #[tokio::test]
-    async fn test_basic_extensions() {
-        let input = vec!["file1.txt", "file2.txt", "image.png", "doc.pdf"];
-        let counts = count_extensions(&input).await;
+    #[test]
+    fn test_basic_extensions() {
+        let counts = count_paths(&["file1.txt", "file2.txt", "image.png", "doc.pdf"]);
         assert_eq!(counts.get("txt").unwrap(), &2);
         assert_eq!(counts.get("png").unwrap(), &1);
         assert_eq!(counts.get("pdf").unwrap(), &1);
     }
 
-    #[tokio::test]
-    async fn test_no_extensions() {
-        let input = vec!["file1", "file2", "README"];
-        let counts = count_extensions(&input).await;
-        assert_eq!(counts.get("").unwrap(), &3);
+    #[test]
+    fn test_no_extensions() {
+        let counts = count_paths(&["file1", "file2"]);
+        assert_eq!(counts.get("").unwrap(), &2);
+    }
+
+    #[test]
+    fn test_well_known_filenames_bucket_separately_through_count_paths_parallel() {
+        let counts = count_paths(&["README", "file1", "Makefile"]);
+        assert_eq!(counts.get("README"), Some(&1));
+        assert_eq!(counts.get("Makefile"), Some(&1));
+        assert_eq!(counts.get(""), Some(&1));
     }
 
-    #[tokio::test]
-    async fn test_mixed_case_extensions() {
-        let input = vec!["file1.TXT", "file2.txt", "image.PNG", "doc.Pdf"];
-        let counts = count_extensions(&input).await;
+    #[test]
+    fn test_mixed_case_extensions() {
+        let counts = count_paths(&["file1.TXT", "file2.txt", "image.PNG", "doc.Pdf"]);
         assert_eq!(counts.get("txt").unwrap(), &2);
         assert_eq!(counts.get("png").unwrap(), &1);
         assert_eq!(counts.get("pdf").unwrap(), &1);
     }
 
-    #[tokio::test]
-    async fn test_empty_input() {
-        let input: Vec<&str> = vec![];
-        let counts = count_extensions(&input).await;
+    #[test]
+    fn test_empty_input() {
+        let counts = count_paths(&[]);
         assert!(counts.is_empty());
     }
 
-    #[tokio::test]
-    async fn test_empty_lines() {
-        let input = vec!["file1.txt", "", "file2.txt", ""];
-        let counts = count_extensions(&input).await;
+    #[test]
+    fn test_empty_lines() {
+        let counts = count_paths(&["file1.txt", "", "file2.txt", ""]);
         assert_eq!(counts.get("txt").unwrap(), &2);
     }
+
+    #[test]
+    fn test_group_by_category_known_extensions() {
+        let mut counts = HashMap::new();
+        counts.insert("png".to_string(), 3);
+        counts.insert("mp3".to_string(), 1);
+
+        let grouped = group_by_category(&counts);
+        assert_eq!(grouped.get("Images"), Some(&3));
+        assert_eq!(grouped.get("Audio"), Some(&1));
+    }
+
+    #[test]
+    fn test_group_by_category_unknown_extension_falls_back_to_other() {
+        let mut counts = HashMap::new();
+        counts.insert("xyz".to_string(), 2);
+
+        let grouped = group_by_category(&counts);
+        assert_eq!(grouped.get("[other]"), Some(&2));
+    }
+
+    #[test]
+    fn test_extension_count_serializes_to_json_objects() {
+        let rows = vec![(".rs".to_string(), 2), ("[no extension]".to_string(), 1)];
+        let entries: Vec<ExtensionCount> = rows
+            .into_iter()
+            .map(|(extension, count)| ExtensionCount { extension, count })
+            .collect();
+        let json = serde_json::to_string(&entries).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"extension":".rs","count":2},{"extension":"[no extension]","count":1}]"#
+        );
+    }
+
+    #[test]
+    fn test_classify_path_skips_empty() {
+        let lookup = build_filename_lookup();
+        assert_eq!(classify_path("", false, &lookup, None, None), None);
+    }
+
+    #[test]
+    fn test_classify_path_buckets_by_extension() {
+        let lookup = build_filename_lookup();
+        let (bucket, mismatch) = classify_path("file.TXT", false, &lookup, None, None).unwrap();
+        assert_eq!(bucket, "txt");
+        assert_eq!(mismatch, None);
+    }
+
+    #[test]
+    fn test_classify_path_known_filename_takes_precedence() {
+        let lookup = build_filename_lookup();
+        let (bucket, _) = classify_path("/project/Makefile", false, &lookup, None, None).unwrap();
+        assert_eq!(bucket, "Makefile");
+    }
+
+    #[test]
+    fn test_classify_path_include_filters_out_non_matching_extensions() {
+        let lookup = build_filename_lookup();
+        let include: HashSet<String> = ["png".to_string()].into_iter().collect();
+        assert_eq!(
+            classify_path("file.txt", false, &lookup, Some(&include), None),
+            None
+        );
+        assert!(classify_path("file.png", false, &lookup, Some(&include), None).is_some());
+    }
+
+    #[test]
+    fn test_classify_path_exclude_filters_out_matching_extensions() {
+        let lookup = build_filename_lookup();
+        let exclude: HashSet<String> = ["png".to_string()].into_iter().collect();
+        assert_eq!(
+            classify_path("file.png", false, &lookup, None, Some(&exclude)),
+            None
+        );
+        assert!(classify_path("file.txt", false, &lookup, None, Some(&exclude)).is_some());
+    }
+
+    #[test]
+    fn test_parse_filter_list_expands_macro_and_trims_dots() {
+        let parsed = parse_filter_list(" IMAGE, .TXT ");
+        assert!(parsed.contains("png"));
+        assert!(parsed.contains("jpg"));
+        assert!(parsed.contains("txt"));
+    }
+
+    #[test]
+    fn test_count_paths_parallel_merges_worker_results() {
+        let lookup = build_filename_lookup();
+        let paths = vec![
+            "a.txt".to_string(),
+            "b.txt".to_string(),
+            "c.png".to_string(),
+            String::new(),
+        ];
+
+        let (counts, mismatches) = count_paths_parallel(&paths, false, &lookup, None, None);
+
+        assert_eq!(counts.get("txt"), Some(&2));
+        assert_eq!(counts.get("png"), Some(&1));
+        assert!(mismatches.is_empty());
+    }
+
+    #[test]
+    fn test_detect_real_ext_png_signature() {
+        let mut path = std::env::temp_dir();
+        path.push("count_exts_test_detect_real_ext.bin");
+        std::fs::write(&path, [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]).unwrap();
+
+        let detected = detect_real_ext(&path);
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(detected.as_deref(), Some("png"));
+    }
+
+    #[test]
+    fn test_detect_real_ext_missing_file() {
+        let path = Path::new("/nonexistent/count_exts_test_missing_file");
+        assert_eq!(detect_real_ext(path), None);
+    }
+
+    #[test]
+    fn test_canonical_ext_normalizes_known_aliases() {
+        assert_eq!(canonical_ext("jpeg"), "jpg");
+        assert_eq!(canonical_ext("jpg"), "jpg");
+        assert_eq!(canonical_ext("tif"), "tiff");
+        assert_eq!(canonical_ext("tiff"), "tiff");
+        assert_eq!(canonical_ext("png"), "png");
+    }
+
+    #[test]
+    fn test_classify_path_does_not_flag_jpeg_jpg_alias_as_mismatch() {
+        let mut path = std::env::temp_dir();
+        path.push("count_exts_test_jpeg_alias.jpeg");
+        std::fs::write(&path, [0xFF, 0xD8, 0xFF, 0xE0]).unwrap();
+
+        let lookup = build_filename_lookup();
+        let (_, mismatch) =
+            classify_path(path.to_str().unwrap(), true, &lookup, None, None).unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(mismatch, None);
+    }
+
+    #[test]
+    fn test_walk_paths_finds_files_recursively() {
+        let mut dir = std::env::temp_dir();
+        dir.push("count_exts_test_walk_paths");
+        let nested = dir.join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.join("a.txt"), b"a").unwrap();
+        std::fs::write(nested.join("b.txt"), b"b").unwrap();
+
+        let found = walk_paths(&[dir.clone()], None, false);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(found.len(), 2);
+    }
+
+    #[test]
+    fn test_classify_filename_matches_known_names_case_insensitively() {
+        let lookup = build_filename_lookup();
+        assert_eq!(
+            classify_filename(Path::new("/project/MAKEFILE"), &lookup),
+            Some("Makefile")
+        );
+        assert_eq!(
+            classify_filename(Path::new("/project/Dockerfile"), &lookup),
+            Some("Dockerfile")
+        );
+    }
+
+    #[test]
+    fn test_classify_filename_no_match_returns_none() {
+        let lookup = build_filename_lookup();
+        assert_eq!(
+            classify_filename(Path::new("/project/main.rs"), &lookup),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_known_filename_label() {
+        assert!(is_known_filename_label("Makefile"));
+        assert!(!is_known_filename_label("rs"));
+        assert!(!is_known_filename_label(""));
+    }
+
+    #[test]
+    fn test_walk_paths_honors_max_depth() {
+        let mut dir = std::env::temp_dir();
+        dir.push("count_exts_test_walk_paths_max_depth");
+        let nested = dir.join("nested");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.join("a.txt"), b"a").unwrap();
+        std::fs::write(nested.join("b.txt"), b"b").unwrap();
+
+        let found = walk_paths(&[dir.clone()], Some(1), false);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(found.len(), 1);
+    }
 }